@@ -270,6 +270,269 @@ impl HttpResponse {
     }
 }
 
+/// The metric types understood by the Prometheus text exposition format.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PromMetricType {
+    Counter,
+    Gauge,
+    Histogram,
+    Summary,
+    Untyped,
+}
+
+impl PromMetricType {
+    /// The keyword used for this type on a `# TYPE` line.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PromMetricType::Counter => "counter",
+            PromMetricType::Gauge => "gauge",
+            PromMetricType::Histogram => "histogram",
+            PromMetricType::Summary => "summary",
+            PromMetricType::Untyped => "untyped",
+        }
+    }
+}
+
+/// A single Prometheus sample, rendered with [`crate::Response::prometheus()`].
+///
+/// Samples of the same metric family are grouped under one `# HELP` / `# TYPE`
+/// header. The family is the sample `name`, except that for histograms the
+/// `_bucket`, `_sum`, and `_count` suffixes are stripped, and for summaries the
+/// `_sum` and `_count` suffixes are: e.g. `latency_bucket{le="0.5"}` and
+/// `latency_count` both belong to the `latency` family. The `# TYPE` comes from
+/// the family's first sample, and the `# HELP` from its first sample with
+/// non-empty help.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PromMetric {
+    pub name: String,
+    pub metric_type: PromMetricType,
+    pub help: String,
+    pub labels: Vec<(String, String)>,
+    pub value: f64,
+}
+
+impl PromMetric {
+    /// The name of the metric family this sample belongs to.
+    pub fn family(&self) -> &str {
+        let suffixes: &[&str] = match self.metric_type {
+            PromMetricType::Histogram => &["_bucket", "_sum", "_count"],
+            PromMetricType::Summary => &["_sum", "_count"],
+            _ => &[],
+        };
+        suffixes
+            .iter()
+            .find_map(|suffix| self.name.strip_suffix(suffix))
+            .unwrap_or(&self.name)
+    }
+
+    pub fn new<T>(name: T, metric_type: PromMetricType, value: f64) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            metric_type,
+            help: String::new(),
+            labels: vec![],
+            value,
+        }
+    }
+
+    pub fn help<T>(mut self, help: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.help = help.into();
+        self
+    }
+
+    pub fn label<T, U>(mut self, key: T, value: U) -> Self
+    where
+        T: Into<String>,
+        U: Into<String>,
+    {
+        self.labels.push((key.into(), value.into()));
+        self
+    }
+}
+
+/// Make `name` a valid metric name (`[a-zA-Z_:][a-zA-Z0-9_:]*`) or, if `colon` is
+/// false, a valid label name (the same without `:`), by replacing disallowed
+/// characters with `_` and prefixing `_` if it would start with a digit.
+fn prometheus_name(name: &str, colon: bool) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || (colon && c == ':') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// Render samples in the Prometheus text exposition format (version 0.0.4).
+pub(crate) fn prometheus_exposition(metrics: &[PromMetric]) -> String {
+    let mut families: Vec<(&str, Vec<&PromMetric>)> = vec![];
+    for metric in metrics {
+        let family = metric.family();
+        match families.iter_mut().find(|(name, _)| *name == family) {
+            Some((_, samples)) => samples.push(metric),
+            None => families.push((family, vec![metric])),
+        }
+    }
+    let mut out = String::new();
+    for (family, samples) in families {
+        let family = prometheus_name(family, true);
+        if let Some(help) = samples.iter().map(|s| &s.help).find(|h| !h.is_empty()) {
+            out.push_str(&format!(
+                "# HELP {family} {}\n",
+                help.replace('\\', "\\\\").replace('\n', "\\n")
+            ));
+        }
+        out.push_str(&format!(
+            "# TYPE {family} {}\n",
+            samples[0].metric_type.as_str()
+        ));
+        for sample in samples {
+            out.push_str(&prometheus_name(&sample.name, true));
+            if !sample.labels.is_empty() {
+                let labels = sample
+                    .labels
+                    .iter()
+                    .map(|(key, value)| {
+                        let value = value
+                            .replace('\\', "\\\\")
+                            .replace('"', "\\\"")
+                            .replace('\n', "\\n");
+                        format!("{}=\"{value}\"", prometheus_name(key, false))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(",");
+                out.push_str(&format!("{{{labels}}}"));
+            }
+            let value = if sample.value.is_nan() {
+                "NaN".to_string()
+            } else if sample.value.is_infinite() {
+                if sample.value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+            } else {
+                sample.value.to_string()
+            };
+            out.push_str(&format!(" {value}\n"));
+        }
+    }
+    out
+}
+
+/// Cross-origin resource sharing (CORS) policy, applied to a response with
+/// [`crate::Response::cors()`] or turned into headers with [`CorsConfig::headers()`].
+///
+/// A wildcard `*` origin cannot be combined with credentials, so when credentials
+/// are allowed, the request's `Origin` (set with [`CorsConfig::request_origin()`])
+/// is reflected back instead, along with `Vary: Origin`. The same applies when a
+/// specific list of origins is allowed. If the request origin is not allowed,
+/// no CORS headers are produced at all and the browser will block the response.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CorsConfig {
+    allowed_origins: Vec<String>,
+    allowed_methods: Vec<String>,
+    allowed_headers: Vec<String>,
+    allow_credentials: bool,
+    max_age: Option<u64>,
+    request_origin: Option<String>,
+}
+
+impl CorsConfig {
+    /// Create a new CorsConfig that allows no origins.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Allow requests from an origin, e.g. `https://example.com`.
+    /// Use `*` to allow any origin.
+    pub fn allow_origin<T>(mut self, origin: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.allowed_origins.push(origin.into());
+        self
+    }
+    /// Set the methods listed in `Access-Control-Allow-Methods`.
+    pub fn allow_methods(mut self, methods: &[http::Method]) -> Self {
+        self.allowed_methods = methods.iter().map(|m| m.to_string()).collect();
+        self
+    }
+    /// Set the request headers listed in `Access-Control-Allow-Headers`.
+    pub fn allow_headers(mut self, headers: &[&str]) -> Self {
+        self.allowed_headers = headers.iter().map(|h| h.to_string()).collect();
+        self
+    }
+    /// Set whether the browser may send credentials (cookies, HTTP authentication)
+    /// with cross-origin requests.
+    pub fn allow_credentials(mut self, allow_credentials: bool) -> Self {
+        self.allow_credentials = allow_credentials;
+        self
+    }
+    /// Set how long, in seconds, a browser may cache the result of a preflight request.
+    pub fn max_age(mut self, max_age: u64) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+    /// Set the `Origin` header of the request being responded to.
+    /// Required to produce any headers unless any origin (`*`) is allowed without credentials.
+    pub fn request_origin<T>(mut self, origin: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.request_origin = Some(origin.into());
+        self
+    }
+    /// The CORS headers to set on a response to the configured request origin.
+    /// Suitable for both preflight (`OPTIONS`) and actual responses.
+    pub fn headers(&self) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        let any_origin = self.allowed_origins.iter().any(|o| o == "*");
+        let allow_origin = if any_origin && !self.allow_credentials {
+            "*".to_string()
+        } else {
+            match &self.request_origin {
+                Some(origin) if any_origin || self.allowed_origins.contains(origin) => {
+                    headers.insert("Vary".to_string(), "Origin".to_string());
+                    origin.clone()
+                }
+                _ => return headers,
+            }
+        };
+        headers.insert("Access-Control-Allow-Origin".to_string(), allow_origin);
+        if self.allow_credentials {
+            headers.insert(
+                "Access-Control-Allow-Credentials".to_string(),
+                "true".to_string(),
+            );
+        }
+        if !self.allowed_methods.is_empty() {
+            headers.insert(
+                "Access-Control-Allow-Methods".to_string(),
+                self.allowed_methods.join(", "),
+            );
+        }
+        if !self.allowed_headers.is_empty() {
+            headers.insert(
+                "Access-Control-Allow-Headers".to_string(),
+                self.allowed_headers.join(", "),
+            );
+        }
+        if let Some(max_age) = self.max_age {
+            headers.insert("Access-Control-Max-Age".to_string(), max_age.to_string());
+        }
+        headers
+    }
+}
+
 /// Part of the [`crate::Response`] type issued by http_server
 #[derive(Clone, Debug, Error, Serialize, Deserialize)]
 pub enum HttpServerError {
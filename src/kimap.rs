@@ -1,12 +1,18 @@
-use crate::eth::{EthError, Provider};
+use crate::eth::{
+    BlockId, BlockNumberOrTag, EthError, EthSubError, EthSubResult, Provider, SubscriptionResult,
+};
 use crate::kimap::contract::getCall;
+use crate::kimap::pausable::pausedCall;
 use crate::net;
 use alloy::rpc::types::request::{TransactionInput, TransactionRequest};
 use alloy::{hex, primitives::keccak256};
 use alloy_primitives::{Address, Bytes, FixedBytes, B256};
 use alloy_sol_types::{SolCall, SolEvent, SolValue};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 /// kimap deployment address on optimism
 pub const KIMAP_ADDRESS: &'static str = "0xcA92476B2483aBD5D82AEBF0b56701Bb2e9be658";
@@ -17,6 +23,10 @@ pub const KIMAP_FIRST_BLOCK: u64 = 123_908_000;
 /// the root hash of kimap, empty bytes32
 pub const KIMAP_ROOT_HASH: &'static str =
     "0x0000000000000000000000000000000000000000000000000000000000000000";
+/// multicall3 deployment address, identical on every chain
+pub const MULTICALL_ADDRESS: &'static str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+/// how long [`Kimap::is_paused()`] reuses a previously-read pause state
+pub const PAUSED_CACHE_DURATION: Duration = Duration::from_secs(10);
 
 /// Sol structures for Kimap requests
 pub mod contract {
@@ -219,6 +229,41 @@ pub mod contract {
     }
 }
 
+/// Sol structures for OpenZeppelin's `Pausable` interface, implemented by some
+/// contracts used alongside the Kimap (the Kimap itself is not known to be pausable).
+pub mod pausable {
+    use alloy_sol_macro::sol;
+
+    sol! {
+        /// Retrieves whether the contract is paused. While paused, writes revert.
+        ///
+        /// Returns:
+        /// - paused: Whether the contract is paused.
+        function paused() external view returns (bool paused);
+    }
+}
+
+/// Sol structures for Multicall3 requests, used to batch Kimap reads
+pub mod multicall {
+    use alloy_sol_macro::sol;
+
+    sol! {
+        struct Call {
+            address target;
+            bytes callData;
+        }
+
+        /// Executes each call in order, reverting if any call reverts.
+        ///
+        /// Returns:
+        /// - blockNumber: The block the calls were executed at.
+        /// - returnData: The return data of each call, in order.
+        function aggregate(
+            Call[] calldata calls
+        ) external payable returns (uint256 blockNumber, bytes[] memory returnData);
+    }
+}
+
 /// A mint log from the kimap, converted to a 'resolved' format using
 /// namespace data saved in the kns_indexer.
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -259,6 +304,39 @@ pub enum DecodeLogError {
     UnresolvedParent(String),
 }
 
+/// Errors that can occur when loading a config with [`Kimap::load_config()`].
+#[derive(Debug, Deserialize, Serialize)]
+pub enum LoadConfigError {
+    /// An error occurred while reading the notes.
+    Eth(EthError),
+    /// The notes could not be deserialized into the config type. Contains the
+    /// deserialization error message.
+    Deserialize(String),
+    /// A note exists but its data is neither tagged (see [`decode_note()`]) nor
+    /// UTF-8. Contains the note's label.
+    UndecodableNote(String),
+}
+
+/// A mint, note, or fact log from the kimap, converted to a 'resolved' format.
+/// Produced by [`NameWatch::handle()`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum KimapLog {
+    Mint(Mint),
+    Note(Note),
+    Fact(Fact),
+}
+
+impl KimapLog {
+    /// The path of the entry the log was emitted under.
+    pub fn parent_path(&self) -> &str {
+        match self {
+            KimapLog::Mint(mint) => &mint.parent_path,
+            KimapLog::Note(note) => &note.parent_path,
+            KimapLog::Fact(fact) => &fact.parent_path,
+        }
+    }
+}
+
 /// Canonical function to determine if a kimap entry is valid. This should
 /// be used whenever reading a new kimap entry from a mints query, because
 /// while most frontends will enforce these rules, it is possible to post
@@ -321,22 +399,134 @@ pub fn namehash(name: &str) -> String {
     format!("0x{}", hex::encode(node))
 }
 
+/// Merkle commitments to sets of kimap names.
+///
+/// Scheme:
+/// - Each name's leaf is `keccak256(namehash(name))`, with the namehash taken as
+///   its 32 raw bytes (see [`crate::kimap::namehash()`]).
+/// - Leaves are sorted ascending and deduplicated, so the root does not depend on
+///   the order or multiplicity of the input names.
+/// - Each parent is `keccak256(min(a, b) ++ max(a, b))`, i.e. pairs are sorted
+///   before hashing, so a proof is just the list of sibling hashes from leaf to root
+///   with no left/right flags. This matches OpenZeppelin's `MerkleProof`.
+/// - When a level has an odd number of nodes, the last one is promoted unchanged.
+/// - The root of an empty set is the zero hash.
+pub mod merkle {
+    use super::namehash;
+    use alloy::primitives::keccak256;
+    use alloy_primitives::B256;
+    use std::str::FromStr;
+
+    /// The leaf hash of a name.
+    pub fn leaf(name: &str) -> B256 {
+        keccak256(B256::from_str(&namehash(name)).unwrap())
+    }
+
+    fn hash_pair(a: B256, b: B256) -> B256 {
+        if a <= b {
+            keccak256([a.as_slice(), b.as_slice()].concat())
+        } else {
+            keccak256([b.as_slice(), a.as_slice()].concat())
+        }
+    }
+
+    /// Every level of the tree, from the sorted leaves up to the root.
+    fn levels(names: &[&str]) -> Vec<Vec<B256>> {
+        let mut leaves: Vec<B256> = names.iter().map(|name| leaf(name)).collect();
+        leaves.sort();
+        leaves.dedup();
+        let mut levels = vec![leaves];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(*a, *b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        levels
+    }
+
+    /// Compute the Merkle root committing to a set of names.
+    pub fn compute_root(names: &[&str]) -> B256 {
+        levels(names)
+            .last()
+            .and_then(|level| level.first().copied())
+            .unwrap_or_default()
+    }
+
+    /// Produce the inclusion proof of `name` in the set `names`, to be checked
+    /// with [`verify_inclusion()`]. Returns `None` if `name` is not in the set.
+    pub fn proof(names: &[&str], name: &str) -> Option<Vec<B256>> {
+        let levels = levels(names);
+        let mut index = levels[0].binary_search(&leaf(name)).ok()?;
+        let mut proof = vec![];
+        for level in &levels[..levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        Some(proof)
+    }
+
+    /// Check that `proof` shows `name` is included in the set committed to by `root`.
+    pub fn verify_inclusion(name: &str, proof: &[B256], root: B256) -> bool {
+        proof
+            .iter()
+            .fold(leaf(name), |node, sibling| hash_pair(node, *sibling))
+            == root
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_inclusion() {
+            let names = ["a.os", "b.os", "c.os", "d.os", "e.os"];
+            let root = compute_root(&names);
+            let mut reversed = names;
+            reversed.reverse();
+            assert_eq!(root, compute_root(&reversed));
+
+            for name in names {
+                let proof = proof(&names, name).unwrap();
+                assert!(verify_inclusion(name, &proof, root));
+                assert!(!verify_inclusion(name, &proof, B256::default()));
+            }
+        }
+
+        #[test]
+        fn test_exclusion() {
+            let names = ["a.os", "b.os", "c.os"];
+            let root = compute_root(&names);
+            assert!(proof(&names, "z.os").is_none());
+            let a_proof = proof(&names, "a.os").unwrap();
+            assert!(!verify_inclusion("z.os", &a_proof, root));
+            assert_eq!(compute_root(&[]), B256::default());
+        }
+    }
+}
+
 /// Decode a mint log from the kimap into a 'resolved' format.
 ///
 /// Uses [`valid_name()`] to check if the name is valid.
 pub fn decode_mint_log(log: &crate::eth::Log) -> Result<Mint, DecodeLogError> {
-    let contract::Note::SIGNATURE_HASH = log.topics()[0] else {
+    let KimapLog::Mint(mint) = decode_log_with_parent(log, String::new())? else {
         return Err(DecodeLogError::UnexpectedTopic(log.topics()[0]));
     };
-    let decoded = contract::Mint::decode_log_data(log.data(), true)
-        .map_err(|e| DecodeLogError::DecodeError(e.to_string()))?;
-    let name = String::from_utf8_lossy(&decoded.label).to_string();
-    if !valid_name(&name) {
-        return Err(DecodeLogError::InvalidName(name));
-    }
     match resolve_parent(log, None) {
-        Some(parent_path) => Ok(Mint { name, parent_path }),
-        None => Err(DecodeLogError::UnresolvedParent(name)),
+        Some(parent_path) => Ok(Mint {
+            parent_path,
+            ..mint
+        }),
+        None => Err(DecodeLogError::UnresolvedParent(mint.name)),
     }
 }
 
@@ -344,42 +534,28 @@ pub fn decode_mint_log(log: &crate::eth::Log) -> Result<Mint, DecodeLogError> {
 ///
 /// Uses [`valid_name()`] to check if the name is valid.
 pub fn decode_note_log(log: &crate::eth::Log) -> Result<Note, DecodeLogError> {
-    let contract::Note::SIGNATURE_HASH = log.topics()[0] else {
+    let KimapLog::Note(note) = decode_log_with_parent(log, String::new())? else {
         return Err(DecodeLogError::UnexpectedTopic(log.topics()[0]));
     };
-    let decoded = contract::Note::decode_log_data(log.data(), true)
-        .map_err(|e| DecodeLogError::DecodeError(e.to_string()))?;
-    let note = String::from_utf8_lossy(&decoded.label).to_string();
-    if !valid_note(&note) {
-        return Err(DecodeLogError::InvalidName(note));
-    }
     match resolve_parent(log, None) {
         Some(parent_path) => Ok(Note {
-            note,
             parent_path,
-            data: decoded.data,
+            ..note
         }),
-        None => Err(DecodeLogError::UnresolvedParent(note)),
+        None => Err(DecodeLogError::UnresolvedParent(note.note)),
     }
 }
 
 pub fn decode_fact_log(log: &crate::eth::Log) -> Result<Fact, DecodeLogError> {
-    let contract::Fact::SIGNATURE_HASH = log.topics()[0] else {
+    let KimapLog::Fact(fact) = decode_log_with_parent(log, String::new())? else {
         return Err(DecodeLogError::UnexpectedTopic(log.topics()[0]));
     };
-    let decoded = contract::Fact::decode_log_data(log.data(), true)
-        .map_err(|e| DecodeLogError::DecodeError(e.to_string()))?;
-    let fact = String::from_utf8_lossy(&decoded.label).to_string();
-    if !valid_fact(&fact) {
-        return Err(DecodeLogError::InvalidName(fact));
-    }
     match resolve_parent(log, None) {
         Some(parent_path) => Ok(Fact {
-            fact,
             parent_path,
-            data: decoded.data,
+            ..fact
         }),
-        None => Err(DecodeLogError::UnresolvedParent(fact)),
+        None => Err(DecodeLogError::UnresolvedParent(fact.fact)),
     }
 }
 
@@ -390,6 +566,31 @@ pub fn resolve_parent(log: &crate::eth::Log, timeout: Option<u64>) -> Option<Str
     net::get_name(&parent_hash, log.block_number, timeout)
 }
 
+/// Given a batch of [`crate::eth::Log`]s (which must be logs from kimap), resolve
+/// the parent name of each distinct parent hash among them.
+///
+/// The indexer resolves one namehash per request, so this makes exactly one request
+/// per *unique* parent, rather than one per log as calling [`resolve_parent()`] on
+/// each would. Each parent is resolved at the latest block it appears in within the
+/// batch. The returned map can be passed to [`net::NameResolver::seed()`].
+pub fn resolve_parents(
+    logs: &[crate::eth::Log],
+    timeout: Option<u64>,
+) -> HashMap<B256, Option<String>> {
+    let mut parents: HashMap<B256, Option<u64>> = HashMap::new();
+    for log in logs {
+        let block = parents.entry(log.topics()[1]).or_insert(log.block_number);
+        *block = (*block).max(log.block_number);
+    }
+    parents
+        .into_iter()
+        .map(|(parent_hash, block)| {
+            let name = net::get_name(parent_hash.to_string(), block, timeout);
+            (parent_hash, name)
+        })
+        .collect()
+}
+
 /// Given a [`crate::eth::Log`] (which must be a log from kimap), resolve the full name
 /// of the new entry or note.
 ///
@@ -423,11 +624,218 @@ pub fn resolve_full_name(log: &crate::eth::Log, timeout: Option<u64>) -> Option<
     Some(format!("{name}.{parent_name}"))
 }
 
+/// Encoding of note data, written as a single tag byte before the payload by
+/// [`encode_note()`] and read back by [`decode_note()`] and [`Kimap::get_note_auto()`].
+///
+/// Tag `0x03` is reserved for CBOR, which is not supported yet: no CBOR decoder is
+/// available to this crate, so notes tagged `0x03` currently decode as
+/// [`DecodedNote::Unrecognized`]. New encodings take tags from `0x04` on.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum NoteEncoding {
+    Raw = 0x00,
+    Utf8 = 0x01,
+    Json = 0x02,
+}
+
+impl NoteEncoding {
+    /// Read the encoding from a note's tag byte. Returns `None` for bytes that
+    /// are not a known tag, including the reserved CBOR tag `0x03`.
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0x00 => Some(NoteEncoding::Raw),
+            0x01 => Some(NoteEncoding::Utf8),
+            0x02 => Some(NoteEncoding::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Note data decoded according to its [`NoteEncoding`] tag.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum DecodedNote {
+    Raw(Bytes),
+    Utf8(String),
+    Json(serde_json::Value),
+    /// The data has no recognized tag, or its payload does not decode as the
+    /// tag declares. Contains the full note data, tag byte included. Notes not
+    /// written with [`encode_note()`] will usually end up here.
+    Unrecognized(Bytes),
+}
+
+/// Tag a note payload with its encoding, producing note data that
+/// [`decode_note()`] can read without knowing the encoding out of band.
+/// The payload must already be encoded, e.g. with `serde_json::to_vec()`.
+/// CBOR cannot be written yet; its tag is reserved (see [`NoteEncoding`]).
+pub fn encode_note(value: &[u8], encoding: NoteEncoding) -> Bytes {
+    let mut data = Vec::with_capacity(value.len() + 1);
+    data.push(encoding as u8);
+    data.extend_from_slice(value);
+    data.into()
+}
+
+/// Decode note data written with [`encode_note()`]. CBOR notes (tag `0x03`, see
+/// [`NoteEncoding`]) are not decoded yet and come back as [`DecodedNote::Unrecognized`].
+///
+/// Note that untagged data whose first byte happens to be a valid tag will be
+/// misread, so only use this on notes whose writers follow the convention.
+pub fn decode_note(data: &[u8]) -> DecodedNote {
+    let unrecognized = || DecodedNote::Unrecognized(Bytes::copy_from_slice(data));
+    let Some((tag, payload)) = data.split_first() else {
+        return unrecognized();
+    };
+    match NoteEncoding::from_tag(*tag) {
+        Some(NoteEncoding::Raw) => DecodedNote::Raw(Bytes::copy_from_slice(payload)),
+        Some(NoteEncoding::Utf8) => match std::str::from_utf8(payload) {
+            Ok(text) => DecodedNote::Utf8(text.to_string()),
+            Err(_) => unrecognized(),
+        },
+        Some(NoteEncoding::Json) => match serde_json::from_slice(payload) {
+            Ok(value) => DecodedNote::Json(value),
+            Err(_) => unrecognized(),
+        },
+        None => unrecognized(),
+    }
+}
+
+/// Assemble note data into the JSON object [`Kimap::load_config()`] deserializes,
+/// keyed by `keys`, leaving out notes that do not exist.
+fn config_object(
+    keys: &[&str],
+    values: Vec<Option<Bytes>>,
+) -> Result<serde_json::Value, LoadConfigError> {
+    let mut config = serde_json::Map::new();
+    for (key, data) in keys.iter().zip(values) {
+        let Some(data) = data else {
+            continue;
+        };
+        let value = match decode_note(&data) {
+            DecodedNote::Json(value) => value,
+            DecodedNote::Utf8(text) => serde_json::Value::String(text),
+            DecodedNote::Raw(bytes) => bytes.to_vec().into(),
+            DecodedNote::Unrecognized(data) => match String::from_utf8(data.to_vec()) {
+                Ok(text) => serde_json::Value::String(text),
+                Err(_) => return Err(LoadConfigError::UndecodableNote(format!("~{key}"))),
+            },
+        };
+        config.insert(key.to_string(), value);
+    }
+    Ok(serde_json::Value::Object(config))
+}
+
+/// Everything about a kimap entry: its token-bound account, owner, and the latest
+/// data of each of its notes, as returned by [`Kimap::entry_snapshot()`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EntrySnapshot {
+    pub tba: Address,
+    pub owner: Address,
+    /// Note labels (e.g. `~ip`) mapped to their latest data.
+    pub notes: HashMap<String, Bytes>,
+}
+
+/// A single subscription to the mints, notes, and facts emitted under a set of
+/// entries, created with [`Kimap::watch_names()`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NameWatch {
+    sub_id: u64,
+    paths: HashMap<B256, String>,
+}
+
+impl NameWatch {
+    /// The ID of the underlying subscription, to be used to unsubscribe.
+    pub fn sub_id(&self) -> u64 {
+        self.sub_id
+    }
+
+    /// Handle the body of a [`crate::Request`] from `eth:distro:sys`, decoding any
+    /// log belonging to this subscription and passing it to `handler`. Each log is
+    /// tagged with the watched path that matched as its [`KimapLog::parent_path()`],
+    /// resolved locally rather than through the indexer.
+    ///
+    /// Bodies belonging to other subscriptions, and logs that fail to decode
+    /// (e.g. with an invalid label), are ignored.
+    ///
+    /// # Returns
+    /// An error if this subscription was closed, in which case the caller should
+    /// call [`Kimap::watch_names()`] again.
+    pub fn handle(
+        &self,
+        body: &[u8],
+        mut handler: impl FnMut(KimapLog),
+    ) -> Result<(), EthSubError> {
+        let Ok(sub_result) = serde_json::from_slice::<EthSubResult>(body) else {
+            return Ok(());
+        };
+        let sub = match sub_result {
+            Ok(sub) if sub.id == self.sub_id => sub,
+            Err(e) if e.id == self.sub_id => return Err(e),
+            _ => return Ok(()),
+        };
+        let SubscriptionResult::Log(log) = sub.result else {
+            return Ok(());
+        };
+        let Some(parent_path) = log.topics().get(1).and_then(|hash| self.paths.get(hash)) else {
+            return Ok(());
+        };
+        if let Ok(kimap_log) = decode_log_with_parent(&log, parent_path.clone()) {
+            handler(kimap_log);
+        }
+        Ok(())
+    }
+}
+
+/// Decode a mint, note, or fact log whose parent path is already known. The
+/// `decode_*_log` functions validate with this before resolving the parent.
+fn decode_log_with_parent(
+    log: &crate::eth::Log,
+    parent_path: String,
+) -> Result<KimapLog, DecodeLogError> {
+    match log.topics()[0] {
+        contract::Mint::SIGNATURE_HASH => {
+            let decoded = contract::Mint::decode_log_data(log.data(), true)
+                .map_err(|e| DecodeLogError::DecodeError(e.to_string()))?;
+            let name = String::from_utf8_lossy(&decoded.label).to_string();
+            if !valid_name(&name) {
+                return Err(DecodeLogError::InvalidName(name));
+            }
+            Ok(KimapLog::Mint(Mint { name, parent_path }))
+        }
+        contract::Note::SIGNATURE_HASH => {
+            let decoded = contract::Note::decode_log_data(log.data(), true)
+                .map_err(|e| DecodeLogError::DecodeError(e.to_string()))?;
+            let note = String::from_utf8_lossy(&decoded.label).to_string();
+            if !valid_note(&note) {
+                return Err(DecodeLogError::InvalidName(note));
+            }
+            Ok(KimapLog::Note(Note {
+                note,
+                parent_path,
+                data: decoded.data,
+            }))
+        }
+        contract::Fact::SIGNATURE_HASH => {
+            let decoded = contract::Fact::decode_log_data(log.data(), true)
+                .map_err(|e| DecodeLogError::DecodeError(e.to_string()))?;
+            let fact = String::from_utf8_lossy(&decoded.label).to_string();
+            if !valid_fact(&fact) {
+                return Err(DecodeLogError::InvalidName(fact));
+            }
+            Ok(KimapLog::Fact(Fact {
+                fact,
+                parent_path,
+                data: decoded.data,
+            }))
+        }
+        topic => Err(DecodeLogError::UnexpectedTopic(topic)),
+    }
+}
+
 /// Helper struct for reading from the kimap.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Kimap {
     pub provider: Provider,
     address: Address,
+    #[serde(skip)]
+    paused_cache: Arc<Mutex<HashMap<Address, (bool, Instant)>>>,
 }
 
 impl Kimap {
@@ -437,7 +845,11 @@ impl Kimap {
     /// * `provider` - A reference to the Provider.
     /// * `address` - The address of the Kimap contract.
     pub fn new(provider: Provider, address: Address) -> Self {
-        Self { provider, address }
+        Self {
+            provider,
+            address,
+            paused_cache: Arc::default(),
+        }
     }
 
     /// Creates a new Kimap instance with the default address and chain ID.
@@ -459,6 +871,15 @@ impl Kimap {
     /// A `Result<(Address, Address, Option<Bytes>), EthError>` representing the TBA, owner,
     /// and value if the entry exists and is a note.
     pub fn get(&self, path: &str) -> Result<(Address, Address, Option<Bytes>), EthError> {
+        self.get_at(path, None)
+    }
+
+    /// Like [`Kimap::get()`], but reads the entry as of `block` (latest if `None`).
+    fn get_at(
+        &self,
+        path: &str,
+        block: Option<BlockId>,
+    ) -> Result<(Address, Address, Option<Bytes>), EthError> {
         let get_call = getCall {
             namehash: FixedBytes::<32>::from_str(&namehash(path))
                 .map_err(|_| EthError::InvalidParams)?,
@@ -469,7 +890,7 @@ impl Kimap {
             .input(TransactionInput::new(get_call.into()))
             .to(self.address);
 
-        let res_bytes = self.provider.call(tx_req, None)?;
+        let res_bytes = self.provider.call(tx_req, block)?;
 
         let res = getCall::abi_decode_returns(&res_bytes, false)
             .map_err(|_| EthError::RpcMalformedResponse)?;
@@ -514,6 +935,306 @@ impl Kimap {
         Ok((res.tba, res.owner, note_data))
     }
 
+    /// Gets a note from the Kimap by its string-formatted name-path and decodes it
+    /// according to its [`NoteEncoding`] tag with [`decode_note()`].
+    ///
+    /// # Returns
+    /// `None` if the note does not exist or holds no data.
+    pub fn get_note_auto(&self, path: &str) -> Result<Option<DecodedNote>, EthError> {
+        let (_tba, _owner, data) = self.get(path)?;
+        Ok(data.map(|data| decode_note(&data)))
+    }
+
+    /// Checks whether a contract used alongside the Kimap (such as a minter for a
+    /// namespace) is paused, per OpenZeppelin's `Pausable`, in which case writes
+    /// through it will revert. Use this to report the namespace as temporarily
+    /// unavailable rather than surfacing a failed transaction.
+    ///
+    /// A contract that does not implement `paused()` cannot be paused, so if the
+    /// call reverts or returns no data, this returns `Ok(false)`. Other RPC errors
+    /// are returned.
+    ///
+    /// Results, including for contracts that do not implement `paused()`, are
+    /// cached per address for [`PAUSED_CACHE_DURATION`], so this can be called
+    /// before every operation without an RPC call each time. Clones of this `Kimap`
+    /// share the cache.
+    pub fn is_paused(&self, contract: Address) -> Result<bool, EthError> {
+        let cached = self
+            .paused_cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&contract)
+            .copied();
+        if let Some((paused, read_at)) = cached {
+            if read_at.elapsed() < PAUSED_CACHE_DURATION {
+                return Ok(paused);
+            }
+        }
+
+        let tx_req = TransactionRequest::default()
+            .input(TransactionInput::new(pausedCall {}.abi_encode().into()))
+            .to(contract);
+
+        let paused = match self.provider.call(tx_req, None) {
+            Ok(res_bytes) => pausedCall::abi_decode_returns(&res_bytes, false)
+                .map(|res| res.paused)
+                .unwrap_or(false),
+            Err(EthError::RpcError(e))
+                if e.code == 3 || e.message.to_lowercase().contains("revert") =>
+            {
+                false
+            }
+            Err(e) => return Err(e),
+        };
+
+        self.paused_cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(contract, (paused, Instant::now()));
+        Ok(paused)
+    }
+
+    /// Gets the data of many entries from the Kimap in a single RPC call, batched
+    /// through the Multicall3 contract at [`MULTICALL_ADDRESS`].
+    ///
+    /// # Returns
+    /// The value of each entry, in the order given, if the entry exists and is a note or fact.
+    fn get_data_batch(&self, paths: &[String]) -> Result<Vec<Option<Bytes>>, EthError> {
+        let calls = paths
+            .iter()
+            .map(|path| {
+                Ok(multicall::Call {
+                    target: self.address,
+                    callData: getCall {
+                        namehash: FixedBytes::<32>::from_str(&namehash(path))
+                            .map_err(|_| EthError::InvalidParams)?,
+                    }
+                    .abi_encode()
+                    .into(),
+                })
+            })
+            .collect::<Result<Vec<_>, EthError>>()?;
+        let aggregate_call = multicall::aggregateCall { calls }.abi_encode();
+
+        let tx_req = TransactionRequest::default()
+            .input(TransactionInput::new(aggregate_call.into()))
+            .to(Address::from_str(MULTICALL_ADDRESS).unwrap());
+
+        let res_bytes = self.provider.call(tx_req, None)?;
+
+        let res = multicall::aggregateCall::abi_decode_returns(&res_bytes, false)
+            .map_err(|_| EthError::RpcMalformedResponse)?;
+
+        res.returnData
+            .iter()
+            .map(|data| {
+                let res = getCall::abi_decode_returns(data, false)
+                    .map_err(|_| EthError::RpcMalformedResponse)?;
+                Ok(if res.data == Bytes::default() {
+                    None
+                } else {
+                    Some(res.data)
+                })
+            })
+            .collect()
+    }
+
+    /// Loads a config struct spread across notes on an entry, reading all of
+    /// them in a single batched RPC call.
+    ///
+    /// Each note in `labels` (e.g. `"~rpc-url"`; the `~` may be omitted) is read
+    /// from `entry_path`, and the notes are assembled into a JSON object keyed by
+    /// label with the `~` stripped, which is then deserialized into `T`. Note data
+    /// is decoded with [`decode_note()`]: [`DecodedNote::Json`] notes become their
+    /// JSON value, [`DecodedNote::Utf8`] notes become strings (so a note written as
+    /// UTF-8 `8080` is the string `"8080"`, not a number), and [`DecodedNote::Raw`]
+    /// notes become arrays of bytes. Untagged notes, such as those written without
+    /// [`encode_note()`], are used as UTF-8 strings, or fail with
+    /// [`LoadConfigError::UndecodableNote`] if they are not UTF-8.
+    ///
+    /// Notes that do not exist become absent fields, so `T` should use `Option` or
+    /// `#[serde(default)]` for any note that may be missing. If the object cannot
+    /// be deserialized into `T`, returns [`LoadConfigError::Deserialize`].
+    pub fn load_config<T>(&self, entry_path: &str, labels: &[&str]) -> Result<T, LoadConfigError>
+    where
+        T: DeserializeOwned,
+    {
+        let keys: Vec<&str> = labels
+            .iter()
+            .map(|label| label.strip_prefix('~').unwrap_or(label))
+            .collect();
+        let paths: Vec<String> = keys
+            .iter()
+            .map(|key| format!("~{key}.{entry_path}"))
+            .collect();
+        let values = self.get_data_batch(&paths).map_err(LoadConfigError::Eth)?;
+
+        serde_json::from_value(config_object(&keys, values)?)
+            .map_err(|e| LoadConfigError::Deserialize(e.to_string()))
+    }
+
+    /// Checks which of a set of names were minted within a block range, using a
+    /// single log query rather than a read per name: the query matches Mint logs
+    /// whose child hash is the namehash of any of the names.
+    ///
+    /// Note that this only reflects mints within `from_block..=to_block`, not current
+    /// ownership or existence: a name minted outside the range reports `false`. Use
+    /// [`KIMAP_FIRST_BLOCK`] as `from_block` to cover all of kimap's history, keeping in
+    /// mind that providers may limit the block range of a single log query.
+    ///
+    /// # Returns
+    /// A map from each of the given names to whether it was minted in the range.
+    pub fn any_minted(
+        &self,
+        names: &[&str],
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<HashMap<String, bool>, EthError> {
+        let mut hashes = HashMap::new();
+        for name in names {
+            let hash = B256::from_str(&namehash(name)).map_err(|_| EthError::InvalidParams)?;
+            hashes.insert(hash, *name);
+        }
+        let mut minted: HashMap<String, bool> =
+            names.iter().map(|name| (name.to_string(), false)).collect();
+        if hashes.is_empty() {
+            return Ok(minted);
+        }
+
+        let filter = self
+            .mint_filter()
+            .topic2(hashes.keys().copied().collect::<Vec<_>>())
+            .from_block(from_block)
+            .to_block(to_block);
+
+        for log in self.provider.get_logs(&filter)? {
+            if let Some(name) = log.topics().get(2).and_then(|hash| hashes.get(hash)) {
+                minted.insert(name.to_string(), true);
+            }
+        }
+        Ok(minted)
+    }
+
+    /// Finds the block at which an entry was first minted, by scanning Mint logs for
+    /// the entry from `search_from` to the latest block. Useful for provenance, e.g.
+    /// displaying registration dates or age-based trust heuristics.
+    ///
+    /// # Returns
+    /// The block number of the earliest matching mint, or `None` if the entry was
+    /// not minted in the searched range.
+    pub fn minted_at(&self, path: &str, search_from: u64) -> Result<Option<u64>, EthError> {
+        let filter = self.entry_mint_filter(path).from_block(search_from);
+        Ok(self
+            .provider
+            .get_logs(&filter)?
+            .iter()
+            .filter_map(|log| log.block_number)
+            .min())
+    }
+
+    /// Subscribes to every mint, note, and fact emitted under any of a set of entries,
+    /// using a single subscription: the entries' namehashes are set as an OR-list on
+    /// the topic1 (parent hash) filter. This is far cheaper for the provider than
+    /// one subscription per entry.
+    ///
+    /// Logs arrive as [`crate::Request`]s from `eth:distro:sys`; pass their bodies
+    /// to [`NameWatch::handle()`] to decode them and dispatch them to a handler.
+    pub fn watch_names(&self, sub_id: u64, paths: &[&str]) -> Result<NameWatch, EthError> {
+        let mut watched = HashMap::new();
+        for path in paths {
+            let hash = B256::from_str(&namehash(path)).map_err(|_| EthError::InvalidParams)?;
+            watched.insert(hash, path.to_string());
+        }
+
+        let filter = crate::eth::Filter::new()
+            .address(self.address)
+            .event_signature(vec![
+                contract::Mint::SIGNATURE_HASH,
+                contract::Note::SIGNATURE_HASH,
+                contract::Fact::SIGNATURE_HASH,
+            ])
+            .topic1(watched.keys().copied().collect::<Vec<_>>());
+
+        self.provider.subscribe(sub_id, filter)?;
+        Ok(NameWatch {
+            sub_id,
+            paths: watched,
+        })
+    }
+
+    /// Derives a deterministic subentry name-path under `parent_path` from a seed,
+    /// e.g. a user ID, so that arbitrary input can name per-user or per-resource
+    /// subentries without being massaged into the restricted label alphabet.
+    ///
+    /// The label is the first 26 characters (130 bits) of the keccak256 hash of the
+    /// seed, encoded in lowercase RFC 4648 base32 (`a-z`, `2-7`), so it always
+    /// passes [`valid_name()`]. The same seed always yields the same name.
+    pub fn derive_subname(&self, parent_path: &str, seed: &[u8]) -> String {
+        const ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+        const LABEL_LEN: usize = 26;
+        let hash = keccak256(seed);
+        let mut label = String::with_capacity(LABEL_LEN);
+        let mut buffer: u16 = 0;
+        let mut bits = 0;
+        for byte in hash.iter() {
+            if label.len() == LABEL_LEN {
+                break;
+            }
+            buffer = (buffer << 8) | *byte as u16;
+            bits += 8;
+            while bits >= 5 && label.len() < LABEL_LEN {
+                bits -= 5;
+                label.push(ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+            }
+            buffer &= (1 << bits) - 1;
+        }
+        format!("{label}.{parent_path}")
+    }
+
+    /// Gets an entry's TBA, owner, and notes as of `to_block`. The TBA and owner are
+    /// read at `to_block`, and the notes are reconstructed from the Note logs
+    /// emitted under the entry within `from_block..=to_block` rather than with one
+    /// read per note.
+    ///
+    /// When a note was written several times in the range, the write with the
+    /// highest block number and log index wins, i.e. the note's value at `to_block`.
+    /// Notes last written before `from_block` will be missing, so use
+    /// [`KIMAP_FIRST_BLOCK`] as `from_block` for a complete snapshot. Notes with
+    /// invalid labels are skipped.
+    pub fn entry_snapshot(
+        &self,
+        path: &str,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<EntrySnapshot, EthError> {
+        let (tba, owner, _data) = self.get_at(
+            path,
+            Some(BlockId::Number(BlockNumberOrTag::Number(to_block))),
+        )?;
+
+        let filter = self
+            .note_filter()
+            .topic1(B256::from_str(&namehash(path)).map_err(|_| EthError::InvalidParams)?)
+            .from_block(from_block)
+            .to_block(to_block);
+
+        let mut logs = self.provider.get_logs(&filter)?;
+        logs.sort_by_key(|log| (log.block_number, log.log_index));
+
+        let mut notes = HashMap::new();
+        for log in logs {
+            let Ok(decoded) = contract::Note::decode_log_data(log.data(), true) else {
+                continue;
+            };
+            let note = String::from_utf8_lossy(&decoded.label).to_string();
+            if valid_note(&note) {
+                notes.insert(note, decoded.data);
+            }
+        }
+
+        Ok(EntrySnapshot { tba, owner, notes })
+    }
+
     /// Create a filter for all mint events.
     pub fn mint_filter(&self) -> crate::eth::Filter {
         crate::eth::Filter::new()
@@ -521,6 +1242,13 @@ impl Kimap {
             .event(contract::Mint::SIGNATURE)
     }
 
+    /// Create a filter for the mint event of a specific entry, by its name-path.
+    /// This function will namehash the path and use it as the topic2 (child hash) filter.
+    pub fn entry_mint_filter(&self, path: &str) -> crate::eth::Filter {
+        self.mint_filter()
+            .topic2(B256::from_str(&namehash(path)).unwrap())
+    }
+
     /// Create a filter for all note events.
     pub fn note_filter(&self) -> crate::eth::Filter {
         crate::eth::Filter::new()
@@ -567,3 +1295,215 @@ impl Kimap {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log<E: SolEvent>(event: &E) -> crate::eth::Log {
+        crate::eth::Log {
+            inner: alloy_primitives::Log {
+                address: Address::ZERO,
+                data: event.encode_log_data(),
+            },
+            ..Default::default()
+        }
+    }
+
+    fn mint_log(parent: B256, label: &str) -> crate::eth::Log {
+        log(&contract::Mint {
+            parenthash: parent,
+            childhash: B256::default(),
+            labelhash: keccak256(label),
+            label: label.as_bytes().to_vec().into(),
+        })
+    }
+
+    fn note_log(parent: B256, label: &str, data: &[u8]) -> crate::eth::Log {
+        log(&contract::Note {
+            parenthash: parent,
+            notehash: B256::default(),
+            labelhash: keccak256(label),
+            label: label.as_bytes().to_vec().into(),
+            data: data.to_vec().into(),
+        })
+    }
+
+    fn fact_log(parent: B256, label: &str, data: &[u8]) -> crate::eth::Log {
+        log(&contract::Fact {
+            parenthash: parent,
+            facthash: B256::default(),
+            labelhash: keccak256(label),
+            label: label.as_bytes().to_vec().into(),
+            data: data.to_vec().into(),
+        })
+    }
+
+    #[test]
+    fn test_decode_log_with_parent() {
+        let parent = B256::from_str(&namehash("app.os")).unwrap();
+
+        let KimapLog::Mint(mint) =
+            decode_log_with_parent(&mint_log(parent, "alice"), "app.os".to_string()).unwrap()
+        else {
+            panic!("expected a mint");
+        };
+        assert_eq!(
+            (mint.name.as_str(), mint.parent_path.as_str()),
+            ("alice", "app.os")
+        );
+
+        let KimapLog::Note(note) =
+            decode_log_with_parent(&note_log(parent, "~ip", b"1.2.3.4"), "app.os".to_string())
+                .unwrap()
+        else {
+            panic!("expected a note");
+        };
+        assert_eq!(note.note, "~ip");
+        assert_eq!(note.data, Bytes::from_static(b"1.2.3.4"));
+
+        let KimapLog::Fact(fact) =
+            decode_log_with_parent(&fact_log(parent, "!id", b"42"), "app.os".to_string()).unwrap()
+        else {
+            panic!("expected a fact");
+        };
+        assert_eq!(fact.fact, "!id");
+        assert_eq!(fact.parent_path, "app.os");
+
+        // labels are validated for the event type they belong to
+        assert!(matches!(
+            decode_log_with_parent(&mint_log(parent, "~ip"), String::new()),
+            Err(DecodeLogError::InvalidName(name)) if name == "~ip"
+        ));
+        assert!(matches!(
+            decode_log_with_parent(&note_log(parent, "ip", b""), String::new()),
+            Err(DecodeLogError::InvalidName(name)) if name == "ip"
+        ));
+
+        // the specific decoders reject other event types before resolving the parent
+        assert!(matches!(
+            decode_mint_log(&note_log(parent, "~ip", b"")),
+            Err(DecodeLogError::UnexpectedTopic(topic)) if topic == contract::Note::SIGNATURE_HASH
+        ));
+        assert!(matches!(
+            decode_note_log(&fact_log(parent, "!id", b"")),
+            Err(DecodeLogError::UnexpectedTopic(_))
+        ));
+        assert!(matches!(
+            decode_fact_log(&mint_log(parent, "alice")),
+            Err(DecodeLogError::UnexpectedTopic(_))
+        ));
+        // and invalid labels before resolving the parent, too
+        assert!(matches!(
+            decode_mint_log(&mint_log(parent, "Alice")),
+            Err(DecodeLogError::InvalidName(_))
+        ));
+    }
+
+    #[test]
+    fn test_name_watch_handle() {
+        let parent = B256::from_str(&namehash("app.os")).unwrap();
+        let watch = NameWatch {
+            sub_id: 7,
+            paths: HashMap::from([(parent, "app.os".to_string())]),
+        };
+        let body = |result: EthSubResult| serde_json::to_vec(&result).unwrap();
+        let sub = |id, log| {
+            body(Ok(crate::eth::EthSub {
+                id,
+                result: SubscriptionResult::Log(Box::new(log)),
+            }))
+        };
+
+        let mut seen = vec![];
+        watch
+            .handle(&sub(7, mint_log(parent, "alice")), |log| seen.push(log))
+            .unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].parent_path(), "app.os");
+
+        // another subscription's logs, and logs under unwatched parents, are ignored
+        watch
+            .handle(&sub(8, mint_log(parent, "bob")), |log| seen.push(log))
+            .unwrap();
+        let other = B256::from_str(&namehash("other.os")).unwrap();
+        watch
+            .handle(&sub(7, mint_log(other, "bob")), |log| seen.push(log))
+            .unwrap();
+        assert_eq!(seen.len(), 1);
+
+        // only this subscription's errors are returned
+        let error = |id| {
+            body(Err(EthSubError {
+                id,
+                error: "closed".to_string(),
+            }))
+        };
+        assert!(watch.handle(&error(8), |log| seen.push(log)).is_ok());
+        let err = watch.handle(&error(7), |log| seen.push(log)).unwrap_err();
+        assert_eq!((err.id, err.error.as_str()), (7, "closed"));
+        assert_eq!(seen.len(), 1);
+    }
+
+    #[test]
+    fn test_note_encoding() {
+        let json = encode_note(br#"{"a":1}"#, NoteEncoding::Json);
+        assert_eq!(json[0], 0x02);
+        assert_eq!(
+            decode_note(&json),
+            DecodedNote::Json(serde_json::json!({"a": 1}))
+        );
+        let text = encode_note("hello".as_bytes(), NoteEncoding::Utf8);
+        assert_eq!(decode_note(&text), DecodedNote::Utf8("hello".to_string()));
+        let bad_json = encode_note(b"{", NoteEncoding::Json);
+        assert_eq!(
+            decode_note(&bad_json),
+            DecodedNote::Unrecognized(bad_json.clone())
+        );
+        assert_eq!(
+            decode_note(&[0x03, 0xa0]),
+            DecodedNote::Unrecognized(Bytes::from(vec![0x03, 0xa0]))
+        );
+        assert_eq!(
+            decode_note(&[0xff, 1]),
+            DecodedNote::Unrecognized(Bytes::from(vec![0xff, 1]))
+        );
+    }
+
+    #[test]
+    fn test_config_object() {
+        let keys = ["port", "name", "flags", "legacy", "missing"];
+        let values = vec![
+            Some(encode_note(b"8080", NoteEncoding::Utf8)),
+            Some(encode_note(br#"{"a":1}"#, NoteEncoding::Json)),
+            Some(encode_note(&[1, 2], NoteEncoding::Raw)),
+            Some(Bytes::from_static(br#"{"a":1}"#)),
+            None,
+        ];
+        assert_eq!(
+            config_object(&keys, values).unwrap(),
+            serde_json::json!({
+                "port": "8080",
+                "name": {"a": 1},
+                "flags": [1, 2],
+                "legacy": "{\"a\":1}",
+            })
+        );
+        match config_object(&["bin"], vec![Some(Bytes::from_static(&[0xff, 0xfe]))]) {
+            Err(LoadConfigError::UndecodableNote(label)) => assert_eq!(label, "~bin"),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_derive_subname() {
+        let kimap = Kimap::new(Provider::new(KIMAP_CHAIN_ID, 5), Address::ZERO);
+        let name = kimap.derive_subname("app.os", b"User ID: 42!");
+        let (label, parent) = name.split_once('.').unwrap();
+        assert_eq!(parent, "app.os");
+        assert_eq!(label.len(), 26);
+        assert!(valid_name(label));
+        assert_eq!(name, kimap.derive_subname("app.os", b"User ID: 42!"));
+        assert_ne!(name, kimap.derive_subname("app.os", b"User ID: 43!"));
+    }
+}
@@ -75,14 +75,18 @@ pub mod scripting;
 mod types;
 pub use types::{
     address::{Address, AddressParseError},
+    api_descriptor::{ApiDescriptor, MethodDescriptor},
     capability::Capability,
+    chunked_response::ChunkedResponse,
+    deferred_response::{DeferredResponse, DeferredResponseError},
+    idempotency_guard::IdempotencyGuard,
     lazy_load_blob::LazyLoadBlob,
     message::{Message, _wit_message_to_message},
     on_exit::OnExit,
     package_id::PackageId,
     process_id::{ProcessId, ProcessIdParseError},
     request::Request,
-    response::Response,
+    response::{parse_tagged, GraphQLError, GraphQLErrorLocation, LongPoll, Response},
     send_error::{SendError, SendErrorKind, _wit_send_error_to_send_error},
 };
 
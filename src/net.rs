@@ -1,6 +1,6 @@
 use crate::{get_blob, Address, NodeId, Request, SendError};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 
 //
 // Networking protocol types and functions for interacting with it
@@ -222,7 +222,24 @@ pub fn get_name<T>(namehash: T, block: Option<u64>, timeout: Option<u64>) -> Opt
 where
     T: Into<String>,
 {
-    let res = Request::to(("our", "kns_indexer", "kns_indexer", "sys"))
+    get_name_from(&IndexerSource::local(), namehash, block, timeout).ok()?
+}
+
+/// Get a [`crate::kimap::Kimap`] entry name from its namehash, asking a specific
+/// indexer. Unlike [`get_name()`], distinguishes between the indexer failing to
+/// respond (`Err`) and the indexer not knowing the name (`Ok(None)`).
+///
+/// Default timeout is 30 seconds.
+pub fn get_name_from<T>(
+    source: &IndexerSource,
+    namehash: T,
+    block: Option<u64>,
+    timeout: Option<u64>,
+) -> Result<Option<String>, SendError>
+where
+    T: Into<String>,
+{
+    let res = Request::to(source.address().clone())
         .body(
             serde_json::to_vec(&IndexerRequests::NamehashToName(NamehashToNameRequest {
                 hash: namehash.into(),
@@ -231,14 +248,121 @@ where
             .unwrap(),
         )
         .send_and_await_response(timeout.unwrap_or(30))
-        .unwrap()
-        .ok()?;
+        .unwrap()?;
 
     let Ok(IndexerResponses::Name(maybe_name)) =
         serde_json::from_slice::<IndexerResponses>(res.body())
     else {
-        return None;
+        return Ok(None);
     };
 
-    maybe_name
+    Ok(maybe_name)
+}
+
+/// A process that answers [`IndexerRequests`], such as the local
+/// `kns_indexer:kns_indexer:sys` or an indexer on another node.
+#[derive(Clone, Debug, Serialize, Deserialize, Hash, Eq, PartialEq)]
+pub struct IndexerSource(pub Address);
+
+impl IndexerSource {
+    /// The `kns_indexer:kns_indexer:sys` process on our own node.
+    pub fn local() -> Self {
+        Self(Address::from(("our", "kns_indexer", "kns_indexer", "sys")))
+    }
+    pub fn address(&self) -> &Address {
+        &self.0
+    }
+}
+
+/// A name resolved by a [`NameResolver`], along with the indexer that resolved it.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResolvedName {
+    pub name: String,
+    pub source: IndexerSource,
+}
+
+/// Resolves [`crate::kimap::Kimap`] namehashes to names, querying a primary
+/// indexer and then, if it has no name or fails to respond, each fallback indexer
+/// in order. Successful resolutions are cached: a namehash always refers to the
+/// same name, so cached entries never go stale.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NameResolver {
+    primary: IndexerSource,
+    fallbacks: Vec<IndexerSource>,
+    timeout: Option<u64>,
+    cache: HashMap<String, ResolvedName>,
+}
+
+impl NameResolver {
+    /// Create a resolver that only queries `primary`.
+    pub fn new(primary: IndexerSource) -> Self {
+        Self::with_fallbacks(primary, vec![])
+    }
+    /// Create a resolver that queries `primary`, then each of `fallbacks` in order.
+    pub fn with_fallbacks(primary: IndexerSource, fallbacks: Vec<IndexerSource>) -> Self {
+        Self {
+            primary,
+            fallbacks,
+            timeout: None,
+            cache: HashMap::new(),
+        }
+    }
+    /// Set the timeout, in seconds, given to *each* indexer queried.
+    /// Defaults to 30 seconds, as in [`get_name()`].
+    pub fn timeout(mut self, timeout: u64) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+    /// Resolve a namehash, consulting the cache first. Returns `None` if no
+    /// indexer could resolve the name.
+    pub fn resolve<T>(&mut self, namehash: T, block: Option<u64>) -> Option<ResolvedName>
+    where
+        T: Into<String>,
+    {
+        let namehash = namehash.into();
+        if let Some(resolved) = self.cache.get(&namehash) {
+            return Some(resolved.clone());
+        }
+        let resolved = std::iter::once(&self.primary)
+            .chain(self.fallbacks.iter())
+            .find_map(|source| {
+                let name = get_name_from(source, namehash.as_str(), block, self.timeout)
+                    .ok()
+                    .flatten()?;
+                Some(ResolvedName {
+                    name,
+                    source: source.clone(),
+                })
+            })?;
+        self.cache.insert(namehash, resolved.clone());
+        Some(resolved)
+    }
+    /// Add already-resolved names to the cache, attributed to `source`, e.g. the
+    /// map returned by [`crate::kimap::resolve_parents()`] (which queries
+    /// [`IndexerSource::local()`]). Unresolved (`None`) entries are skipped.
+    pub fn seed<I, K>(&mut self, source: &IndexerSource, names: I)
+    where
+        I: IntoIterator<Item = (K, Option<String>)>,
+        K: ToString,
+    {
+        for (namehash, name) in names {
+            if let Some(name) = name {
+                self.cache.insert(
+                    namehash.to_string(),
+                    ResolvedName {
+                        name,
+                        source: source.clone(),
+                    },
+                );
+            }
+        }
+    }
+    /// Get a cached resolution without querying any indexer.
+    pub fn cached(&self, namehash: &str) -> Option<&ResolvedName> {
+        self.cache.get(namehash)
+    }
+    /// Clear all cached resolutions.
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
 }
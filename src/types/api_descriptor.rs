@@ -0,0 +1,81 @@
+use crate::Capability;
+use serde::{Deserialize, Serialize};
+
+/// A machine-readable description of the API a process serves, sent with
+/// [`crate::Response::capabilities_descriptor()`] so that generic clients or a
+/// service registry can introspect the process: which methods it supports,
+/// the shapes of their IPC bodies, and which [`Capability`]s each requires.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ApiDescriptor {
+    pub name: String,
+    pub version: Option<String>,
+    pub methods: Vec<MethodDescriptor>,
+}
+
+impl ApiDescriptor {
+    pub fn new<T>(name: T) -> Self
+    where
+        T: Into<String>,
+    {
+        ApiDescriptor {
+            name: name.into(),
+            version: None,
+            methods: vec![],
+        }
+    }
+    pub fn version<T>(mut self, version: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.version = Some(version.into());
+        self
+    }
+    pub fn method(mut self, method: MethodDescriptor) -> Self {
+        self.methods.push(method);
+        self
+    }
+}
+
+/// A single method in an [`ApiDescriptor`]. The `request` and `response` shapes
+/// are free-form JSON, e.g. an example body or a JSON Schema, by agreement
+/// between the process and its clients.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MethodDescriptor {
+    pub name: String,
+    pub description: Option<String>,
+    pub request: serde_json::Value,
+    /// `None` if the method does not send a [`crate::Response`].
+    pub response: Option<serde_json::Value>,
+    /// The [`Capability`]s a caller must attach to its [`crate::Request`].
+    pub required_capabilities: Vec<Capability>,
+}
+
+impl MethodDescriptor {
+    pub fn new<T>(name: T, request: serde_json::Value) -> Self
+    where
+        T: Into<String>,
+    {
+        MethodDescriptor {
+            name: name.into(),
+            description: None,
+            request,
+            response: None,
+            required_capabilities: vec![],
+        }
+    }
+    pub fn description<T>(mut self, description: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.description = Some(description.into());
+        self
+    }
+    pub fn response(mut self, response: serde_json::Value) -> Self {
+        self.response = Some(response);
+        self
+    }
+    pub fn requires(mut self, capability: Capability) -> Self {
+        self.required_capabilities.push(capability);
+        self
+    }
+}
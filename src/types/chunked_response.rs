@@ -0,0 +1,43 @@
+use crate::{types::message::BuildError, Response};
+
+/// A [`Response`] whose [`crate::LazyLoadBlob`] is produced incrementally, for
+/// handlers that generate output piece by piece without knowing its total size
+/// in advance (e.g. streaming query results row by row as they arrive).
+/// Start one with [`Response::chunked()`], call [`ChunkedResponse::write_chunk()`]
+/// as data is produced, then [`ChunkedResponse::finish()`] to send.
+///
+/// *Note that nothing is sent until `finish()`: every chunk is buffered in memory,
+/// so the full blob is held by this process before it is sent in one piece.*
+pub struct ChunkedResponse {
+    response: Response,
+    bytes: Vec<u8>,
+}
+
+impl ChunkedResponse {
+    /// Start accumulating chunks for `response`. The body, metadata, MIME type,
+    /// and capabilities of `response` are kept; any blob bytes already set on it
+    /// will be replaced by the chunks when sent.
+    pub fn new(response: Response) -> Self {
+        ChunkedResponse {
+            response,
+            bytes: vec![],
+        }
+    }
+    /// Append a chunk to the blob.
+    pub fn write_chunk(&mut self, chunk: &[u8]) {
+        self.bytes.extend_from_slice(chunk);
+    }
+    /// The number of bytes buffered so far.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+    /// Whether no bytes have been buffered yet.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+    /// Set the buffered chunks as the blob bytes and send the [`Response`].
+    /// This will only fail if the IPC body of the [`Response`] has not been set.
+    pub fn finish(self) -> Result<(), BuildError> {
+        self.response.blob_bytes(self.bytes).send()
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{types::message::merge_metadata, Address, Response};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// A handle for answering a [`crate::Request`] after handling of it has
+/// returned, e.g. once offloaded asynchronous work completes. Obtain one with
+/// [`crate::Message::defer()`] when the request arrives, store it, and call
+/// [`DeferredResponse::resolve()`] when the result is ready.
+///
+/// The kernel only routes a [`Response`] to the sender of the request this
+/// process most recently received, so by the time a deferred result is ready,
+/// a [`Response`] would go to the wrong process. Instead, `resolve()` sends the
+/// result to the original requester as a [`crate::Request`] carrying the
+/// `Response`'s body, blob, and capabilities (but not its `inherit` flag, which
+/// would attach the blob of whatever message arrived last).
+///
+/// Deferral is opt-in on the requester's side: it sends its request without
+/// `expects_response` and with a correlation id set by
+/// [`crate::Request::deferred()`]. The result carries the same id in its metadata,
+/// so the requester can match it to the request with
+/// [`crate::Message::deferred_id()`]. The requester may also say how long it will
+/// wait. After that deadline it has given up, so `resolve()` drops the result and
+/// returns [`DeferredResponseError::Expired`] rather than deliver a stale answer.
+#[derive(Clone, Debug)]
+pub struct DeferredResponse {
+    requester: Address,
+    id: String,
+    deadline: Option<Instant>,
+}
+
+impl DeferredResponse {
+    /// Create a handle for a request from `requester`, received now, with the
+    /// requester's correlation `id` and how long it will wait in seconds, if limited.
+    pub fn new(requester: Address, id: &str, timeout: Option<u64>) -> Self {
+        DeferredResponse {
+            requester,
+            id: id.to_string(),
+            deadline: timeout.map(|secs| Instant::now() + Duration::from_secs(secs)),
+        }
+    }
+    /// The [`Address`] the deferred result will be sent to.
+    pub fn requester(&self) -> &Address {
+        &self.requester
+    }
+    /// The requester's correlation id, sent back with the result.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+    /// The time left before the requester stops waiting, or `None` if it waits
+    /// indefinitely. Measured from when the handle was created, so create it as
+    /// soon as the request is received.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+    /// Whether the requester has stopped waiting.
+    pub fn is_expired(&self) -> bool {
+        self.remaining() == Some(Duration::ZERO)
+    }
+    /// Send `response` to the original requester, as described on [`DeferredResponse`].
+    /// The correlation id is set as `{"deferred_id": id}` in the metadata, added to
+    /// the `Response`'s metadata if that is a JSON object and replacing it otherwise.
+    /// Fails if the requester has stopped waiting or if the body of `response` has
+    /// not been set.
+    pub fn resolve(self, response: Response) -> Result<(), DeferredResponseError> {
+        if self.is_expired() {
+            return Err(DeferredResponseError::Expired);
+        }
+        let mut request = response.into_request(self.requester);
+        request.metadata = Some(merge_metadata(
+            request.metadata.as_deref(),
+            [("deferred_id", self.id.into())],
+        ));
+        // the target is always set, so sending can only fail for lack of a body
+        request.send().map_err(|_| DeferredResponseError::NoBody)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum DeferredResponseError {
+    Expired,
+    NoBody,
+}
+
+impl std::fmt::Display for DeferredResponseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DeferredResponseError::Expired => write!(f, "requester stopped waiting for response"),
+            DeferredResponseError::NoBody => write!(f, "no body set for deferred response"),
+        }
+    }
+}
+
+impl std::error::Error for DeferredResponseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Message;
+
+    fn request(expects_response: Option<u64>, metadata: Option<&str>) -> Message {
+        Message::Request {
+            source: Address::new("our", ("requester", "app", "sys")),
+            expects_response,
+            body: vec![],
+            metadata: metadata.map(|m| m.to_string()),
+            capabilities: vec![],
+        }
+    }
+
+    #[test]
+    fn test_deadline() {
+        let requester = Address::new("our", ("requester", "app", "sys"));
+
+        let unlimited = DeferredResponse::new(requester.clone(), "a", None);
+        assert_eq!(unlimited.id(), "a");
+        assert_eq!(unlimited.requester(), &requester);
+        assert_eq!(unlimited.remaining(), None);
+        assert!(!unlimited.is_expired());
+
+        let pending = DeferredResponse::new(requester.clone(), "b", Some(60));
+        let remaining = pending.remaining().unwrap();
+        assert!(remaining > Duration::from_secs(59) && remaining <= Duration::from_secs(60));
+        assert!(!pending.is_expired());
+
+        let expired = DeferredResponse::new(requester, "c", Some(0));
+        assert_eq!(expired.remaining(), Some(Duration::ZERO));
+        assert!(expired.is_expired());
+    }
+
+    #[test]
+    fn test_defer() {
+        let deferred = request(None, Some(r#"{"deferred_id":"a","deferred_timeout":30}"#))
+            .defer()
+            .unwrap();
+        assert_eq!(deferred.id(), "a");
+        assert!(deferred.remaining().unwrap() <= Duration::from_secs(30));
+
+        let deferred = request(None, Some(r#"{"deferred_id":"b"}"#))
+            .defer()
+            .unwrap();
+        assert_eq!(deferred.remaining(), None);
+
+        // existing JSON-object metadata is kept
+        let metadata = crate::Request::new()
+            .metadata(r#"{"trace":"t1"}"#)
+            .deferred("d", Some(10))
+            .metadata;
+        let deferred = request(None, metadata.as_deref()).defer().unwrap();
+        assert_eq!(deferred.id(), "d");
+        assert!(deferred.remaining().is_some());
+        let metadata: serde_json::Value = serde_json::from_str(&metadata.unwrap()).unwrap();
+        assert_eq!(metadata["trace"], "t1");
+
+        // no correlation id, or a requester awaiting a real response
+        assert!(request(None, None).defer().is_none());
+        assert!(request(None, Some("plain")).defer().is_none());
+        assert!(request(Some(5), Some(r#"{"deferred_id":"c"}"#))
+            .defer()
+            .is_none());
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Receiver-side deduplication for at-least-once delivery. Records the
+/// idempotency keys of messages already processed (see
+/// [`crate::Request::idempotency_key()`], [`crate::Response::idempotency_key()`], and
+/// [`crate::Message::idempotency_key()`])
+/// so that redelivered duplicates can be dropped.
+///
+/// Keys are remembered for a time window, after which a repeat is treated as new.
+/// Memory is bounded: at most `capacity` keys are held, and when full, the oldest
+/// key is forgotten early to make room.
+#[derive(Clone, Debug)]
+pub struct IdempotencyGuard {
+    window: Duration,
+    capacity: usize,
+    seen: HashMap<String, Instant>,
+    order: VecDeque<(String, Instant)>,
+}
+
+impl IdempotencyGuard {
+    /// Create a guard remembering keys for `window`, holding at most `capacity`
+    /// keys (at least one).
+    pub fn new(window: Duration, capacity: usize) -> Self {
+        IdempotencyGuard {
+            window,
+            capacity: capacity.max(1),
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+    /// Check whether `key` has been seen within the window, recording it if not.
+    /// Returns `true` if the key is new and the message should be processed,
+    /// `false` if it is a duplicate and should be dropped.
+    pub fn check_and_record(&mut self, key: &str) -> bool {
+        let now = Instant::now();
+        while let Some((_, recorded_at)) = self.order.front() {
+            if now.duration_since(*recorded_at) < self.window {
+                break;
+            }
+            self.evict_oldest();
+        }
+        if self.seen.contains_key(key) {
+            return false;
+        }
+        while self.seen.len() >= self.capacity {
+            self.evict_oldest();
+        }
+        self.seen.insert(key.to_string(), now);
+        self.order.push_back((key.to_string(), now));
+        true
+    }
+    /// The number of keys currently remembered.
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+    /// Whether no keys are currently remembered.
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+    fn evict_oldest(&mut self) {
+        if let Some((key, recorded_at)) = self.order.pop_front() {
+            if self.seen.get(&key) == Some(&recorded_at) {
+                self.seen.remove(&key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup() {
+        let mut guard = IdempotencyGuard::new(Duration::from_secs(60), 10);
+        assert!(guard.check_and_record("a"));
+        assert!(!guard.check_and_record("a"));
+        assert!(guard.check_and_record("b"));
+        assert_eq!(guard.len(), 2);
+    }
+
+    #[test]
+    fn test_eviction() {
+        let mut guard = IdempotencyGuard::new(Duration::from_secs(60), 2);
+        assert!(guard.check_and_record("a"));
+        assert!(guard.check_and_record("b"));
+        assert!(guard.check_and_record("c"));
+        assert_eq!(guard.len(), 2);
+        // "a" was evicted to make room, so it is treated as new again
+        assert!(guard.check_and_record("a"));
+        assert!(!guard.check_and_record("c"));
+
+        let mut guard = IdempotencyGuard::new(Duration::ZERO, 10);
+        assert!(guard.check_and_record("a"));
+        assert!(guard.check_and_record("a"));
+        assert_eq!(guard.len(), 1);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Address, Capability, LazyLoadBlob, ProcessId};
+use crate::{Address, Capability, DeferredResponse, LazyLoadBlob, ProcessId};
 use serde::{Deserialize, Serialize};
 
 /// The basic `Message` type.
@@ -65,6 +65,19 @@ impl Message {
             Message::Response { metadata, .. } => metadata.as_ref().map(|s| s.as_str()),
         }
     }
+    /// Get the idempotency key of a `Message`, if its metadata carries one, as set
+    /// by [`crate::Request::idempotency_key()`] or [`crate::Response::idempotency_key()`].
+    pub fn idempotency_key(&self) -> Option<String> {
+        let metadata = serde_json::from_str::<serde_json::Value>(self.metadata()?).ok()?;
+        Some(metadata.get("idempotency_key")?.as_str()?.to_string())
+    }
+    /// Get the deferred-response correlation id of a `Message`, if its metadata
+    /// carries one, as set by [`crate::Request::deferred()`] or by
+    /// [`DeferredResponse::resolve()`].
+    pub fn deferred_id(&self) -> Option<String> {
+        let metadata = serde_json::from_str::<serde_json::Value>(self.metadata()?).ok()?;
+        Some(metadata.get("deferred_id")?.as_str()?.to_string())
+    }
     /// Get the context of a `Message`. Always `None` for requests.
     pub fn context(&self) -> Option<&[u8]> {
         match self {
@@ -89,6 +102,27 @@ impl Message {
     pub fn is_request(&self) -> bool {
         matches!(self, Message::Request { .. })
     }
+    /// Get a [`DeferredResponse`] handle for answering this `Message` after
+    /// handling of it has returned. Returns `None` unless this `Message` is a
+    /// [`crate::Request`] sent with [`crate::Request::deferred()`] and without
+    /// `expects_response`, since a requester awaiting a real [`crate::Response`]
+    /// would time out instead of receiving the deferred result.
+    pub fn defer(&self) -> Option<DeferredResponse> {
+        let Message::Request {
+            source,
+            expects_response: None,
+            ..
+        } = self
+        else {
+            return None;
+        };
+        let metadata = serde_json::from_str::<serde_json::Value>(self.metadata()?).ok()?;
+        Some(DeferredResponse::new(
+            source.clone(),
+            metadata.get("deferred_id")?.as_str()?,
+            metadata.get("deferred_timeout").and_then(|t| t.as_u64()),
+        ))
+    }
     /// Check if a `Message` was sent by a local process.
     /// Returns `false` if the `source` node is not our local node.
     pub fn is_local(&self, our: &Address) -> bool {
@@ -110,6 +144,25 @@ impl Message {
     }
 }
 
+/// Add `fields` to `metadata` if it is a JSON object, or start a new object
+/// otherwise (replacing non-object metadata), and serialize the result.
+pub(crate) fn merge_metadata(
+    metadata: Option<&str>,
+    fields: impl IntoIterator<Item = (&'static str, serde_json::Value)>,
+) -> String {
+    let mut map = metadata
+        .and_then(|metadata| serde_json::from_str::<serde_json::Value>(metadata).ok())
+        .and_then(|metadata| match metadata {
+            serde_json::Value::Object(map) => Some(map),
+            _ => None,
+        })
+        .unwrap_or_default();
+    for (key, value) in fields {
+        map.insert(key.to_string(), value);
+    }
+    serde_json::Value::Object(map).to_string()
+}
+
 pub fn _wit_message_to_message(
     source: Address,
     message: crate::kinode::process::standard::Message,
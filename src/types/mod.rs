@@ -1,5 +1,9 @@
 pub mod address;
+pub mod api_descriptor;
 pub mod capability;
+pub mod chunked_response;
+pub mod deferred_response;
+pub mod idempotency_guard;
 pub mod lazy_load_blob;
 pub mod message;
 pub mod on_exit;
@@ -128,6 +128,39 @@ impl Request {
         self.metadata = Some(metadata.to_string());
         self
     }
+    /// Set the metadata of this request to carry an idempotency key, as an
+    /// `idempotency_key` field of a JSON object. The field is added to any metadata
+    /// already set that is a JSON object, and replaces other metadata.
+    /// A receiver can read it with [`crate::Message::idempotency_key()`] and drop
+    /// duplicates, e.g. of a retried request, with an [`crate::IdempotencyGuard`].
+    pub fn idempotency_key(mut self, key: &str) -> Self {
+        self.metadata = Some(crate::types::message::merge_metadata(
+            self.metadata.as_deref(),
+            [("idempotency_key", key.into())],
+        ));
+        self
+    }
+    /// Set the metadata of this request to ask for a deferred result, as
+    /// `deferred_id` and (if `timeout` is set) `deferred_timeout` fields of a JSON
+    /// object. The fields are added to any metadata already set that is a JSON
+    /// object, and replace other metadata. `timeout` is how long, in seconds, this
+    /// process will wait for the result.
+    ///
+    /// The receiver answers with [`crate::DeferredResponse`], which sends the result
+    /// back as a request carrying the same `id`, read with
+    /// [`crate::Message::deferred_id()`]. Send this request without
+    /// [`Request::expects_response()`], or the receiver will not defer it.
+    pub fn deferred(mut self, id: &str, timeout: Option<u64>) -> Self {
+        let mut fields = vec![("deferred_id", id.into())];
+        if let Some(timeout) = timeout {
+            fields.push(("deferred_timeout", timeout.into()));
+        }
+        self.metadata = Some(crate::types::message::merge_metadata(
+            self.metadata.as_deref(),
+            fields,
+        ));
+        self
+    }
     /// Set the blob of this request. A [`LazyLoadBlob`] holds bytes and an optional
     /// MIME type.
     ///
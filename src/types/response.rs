@@ -1,4 +1,9 @@
-use crate::{types::message::BuildError, Capability, LazyLoadBlob};
+use crate::{
+    http::server::{CorsConfig, HttpResponse, PromMetric},
+    types::message::{merge_metadata, BuildError},
+    Address, ApiDescriptor, Capability, ChunkedResponse, LazyLoadBlob, Request,
+};
+use serde::{Deserialize, Serialize};
 
 /// `Response` builder. Use [`Response::new()`] to start a `Response`, then build it,
 /// then call [`Response::send()`] on it to fire.
@@ -145,11 +150,221 @@ impl Response {
             Ok(self)
         }
     }
+    /// Set the [`LazyLoadBlob`] to a set of metrics rendered in the Prometheus text
+    /// exposition format, with the `text/plain; version=0.0.4` MIME type, for a
+    /// process serving a metrics endpoint to a Prometheus scraper.
+    ///
+    /// Samples of the same family (see [`PromMetric`]; histogram and summary
+    /// `_bucket`, `_sum`, and `_count` suffixes are stripped) are grouped under a
+    /// single `# HELP` and `# TYPE` line. Characters not allowed in metric and label
+    /// names are replaced with `_`, and label values are escaped (backslash,
+    /// double-quote, and newline) as the format requires.
+    pub fn prometheus(self, metrics: &[PromMetric]) -> Self {
+        self.blob(LazyLoadBlob {
+            mime: Some("text/plain; version=0.0.4".to_string()),
+            bytes: crate::http::server::prometheus_exposition(metrics).into_bytes(),
+        })
+    }
+    /// Set the IPC body to a GraphQL response envelope: a JSON object with a `data`
+    /// field holding the serialized `data` and, if any errors are given, an `errors`
+    /// field holding them. Per the GraphQL spec, `data` should be `None` (serialized
+    /// as `null`) when a top-level error prevented execution, and `errors` is omitted
+    /// entirely when empty.
+    pub fn graphql<T>(mut self, data: Option<&T>, errors: &[GraphQLError]) -> anyhow::Result<Self>
+    where
+        T: Serialize,
+    {
+        let mut envelope = serde_json::Map::new();
+        envelope.insert("data".to_string(), serde_json::to_value(data)?);
+        if !errors.is_empty() {
+            envelope.insert("errors".to_string(), serde_json::to_value(errors)?);
+        }
+        self.body = Some(serde_json::to_vec(&envelope)?);
+        Ok(self)
+    }
+    /// Set CORS headers on this `Response` according to a [`CorsConfig`].
+    /// See [`Response::http_headers()`] for how headers are attached.
+    pub fn cors(self, config: CorsConfig) -> Self {
+        self.http_headers(config.headers())
+    }
+    /// Add headers to the [`HttpResponse`] held in the IPC body of this `Response`,
+    /// as sent in reply to an [`crate::http::server::IncomingHttpRequest`]. Headers
+    /// already present with the same name (compared case-insensitively) are replaced,
+    /// except `Vary`, whose tokens are merged with any already present so that
+    /// helpers like [`Response::static_asset()`] and [`Response::cors()`] compose.
+    ///
+    /// If the IPC body is not set, it is set to a `200 OK` [`HttpResponse`] carrying
+    /// these headers. If it is set to something other than an [`HttpResponse`], it
+    /// is left untouched and the headers are not added.
+    pub fn http_headers<I, K, V>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let mut http_response = match &self.body {
+            None => HttpResponse::new(200u16),
+            Some(body) => match serde_json::from_slice::<HttpResponse>(body) {
+                Ok(http_response) => http_response,
+                Err(_) => return self,
+            },
+        };
+        for (key, value) in headers {
+            let (key, mut value): (String, String) = (key.into(), value.into());
+            let existing = http_response
+                .headers
+                .keys()
+                .find(|existing| existing.eq_ignore_ascii_case(&key))
+                .cloned();
+            if let Some(existing) = existing {
+                let existing_value = http_response.headers.remove(&existing).unwrap();
+                if key.eq_ignore_ascii_case("vary") {
+                    value = merge_vary(&existing_value, &value);
+                }
+            }
+            http_response.headers.insert(key, value);
+        }
+        self.body = Some(serde_json::to_vec(&http_response).unwrap());
+        self
+    }
+    /// Set the IPC body to [`LongPoll::Timeout`], telling a long-polling client that
+    /// no data arrived before the poll timed out and it should poll again.
+    pub fn long_poll_timeout(mut self) -> Self {
+        self.body = Some(serde_json::to_vec(&LongPoll::<()>::Timeout).unwrap());
+        self
+    }
+    /// Set the IPC body to [`LongPoll::Data`] holding `data`, answering a
+    /// long-polling client. Unlike [`Response::long_poll_timeout()`], this is a
+    /// final result even if `data` itself is empty.
+    pub fn long_poll_data<T>(mut self, data: &T) -> anyhow::Result<Self>
+    where
+        T: Serialize,
+    {
+        self.body = Some(serde_json::to_vec(&LongPoll::Data(data))?);
+        Ok(self)
+    }
+    /// Serve a static asset stored both as-is (`identity`) and, optionally,
+    /// precompressed with gzip, so that it need not be compressed per request.
+    ///
+    /// The gzipped bytes are served, with a `Content-Encoding: gzip` header, if
+    /// they are given and the client's `Accept-Encoding` header value accepts gzip
+    /// (honoring `q=0` and `*`). Otherwise the identity bytes are served. Either
+    /// way, the blob is set with the given MIME type, and a `Content-Type` header is
+    /// added (plus `Vary: Accept-Encoding` if a gzipped version exists), as with
+    /// [`Response::http_headers()`].
+    pub fn static_asset(
+        self,
+        identity: &[u8],
+        gzipped: Option<&[u8]>,
+        accept_encoding: &str,
+        mime: &str,
+    ) -> Self {
+        let mut headers = vec![("Content-Type", mime)];
+        let bytes = match gzipped {
+            Some(gzipped) => {
+                headers.push(("Vary", "Accept-Encoding"));
+                if accepts_gzip(accept_encoding) {
+                    headers.push(("Content-Encoding", "gzip"));
+                    gzipped
+                } else {
+                    identity
+                }
+            }
+            None => identity,
+        };
+        self.http_headers(headers).blob(LazyLoadBlob {
+            mime: Some(mime.to_string()),
+            bytes: bytes.to_vec(),
+        })
+    }
+    /// Set the IPC body to an [`ApiDescriptor`] serialized to JSON, describing the
+    /// methods this process supports and the capabilities each requires.
+    pub fn capabilities_descriptor(mut self, descriptor: &ApiDescriptor) -> anyhow::Result<Self> {
+        self.body = Some(serde_json::to_vec(descriptor)?);
+        Ok(self)
+    }
+    /// Set the IPC body to a tagged payload, `{"type": tag, "payload": payload}`
+    /// serialized to JSON, so that clients can dispatch on `type` before
+    /// deserializing the payload. Read it back with [`parse_tagged()`].
+    ///
+    /// This is the same shape serde produces for an enum marked
+    /// `#[serde(tag = "type", content = "payload")]`, so Rust clients can also
+    /// deserialize the body straight into such an enum.
+    pub fn tagged<T>(mut self, tag: &str, payload: &T) -> anyhow::Result<Self>
+    where
+        T: Serialize,
+    {
+        self.body = Some(serde_json::to_vec(&serde_json::json!({
+            "type": tag,
+            "payload": payload,
+        }))?);
+        Ok(self)
+    }
+    /// Set the metadata of this `Response` to carry an idempotency key, as an
+    /// `idempotency_key` field of a JSON object. The field is added to any metadata
+    /// already set that is a JSON object, and replaces other metadata.
+    /// A receiver can read it with [`crate::Message::idempotency_key()`] and drop
+    /// duplicates with an [`crate::IdempotencyGuard`].
+    pub fn idempotency_key(mut self, key: &str) -> Self {
+        self.metadata = Some(merge_metadata(
+            self.metadata.as_deref(),
+            [("idempotency_key", key.into())],
+        ));
+        self
+    }
+    /// Set the `Server-Timing` header, as with [`Response::http_headers()`], so that
+    /// browser devtools can show how long this process spent on each phase of
+    /// handling the request. Each `(name, milliseconds)` pair is formatted as
+    /// `name;dur=milliseconds`, e.g. `Server-Timing: db;dur=53.2, render;dur=4`.
+    ///
+    /// Characters not allowed in a metric name (an HTTP token) are replaced with `_`,
+    /// and entries with an empty name or a non-finite duration are skipped. Calling
+    /// this again replaces the previous header.
+    pub fn server_timing(self, timings: &[(&str, f64)]) -> Self {
+        let value = timings
+            .iter()
+            .filter(|(name, dur)| !name.is_empty() && dur.is_finite())
+            .map(|(name, dur)| {
+                let name: String = name
+                    .chars()
+                    .map(|c| {
+                        if c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c) {
+                            c
+                        } else {
+                            '_'
+                        }
+                    })
+                    .collect();
+                format!("{name};dur={dur}")
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.http_headers([("Server-Timing", value)])
+    }
     /// Attach capabilities to this next `Response`.
     pub fn capabilities(mut self, capabilities: Vec<Capability>) -> Self {
         self.capabilities = capabilities;
         self
     }
+    /// Turn this `Response` into a [`ChunkedResponse`], to build its blob
+    /// incrementally before sending.
+    pub fn chunked(self) -> ChunkedResponse {
+        ChunkedResponse::new(self)
+    }
+    /// Convert this `Response` into a [`Request`] to `target` carrying the same
+    /// body, metadata, blob, and capabilities. Used by [`crate::DeferredResponse`].
+    pub(crate) fn into_request(self, target: Address) -> Request {
+        Request {
+            target: Some(target),
+            inherit: false,
+            timeout: None,
+            body: self.body,
+            metadata: self.metadata,
+            blob: self.blob,
+            context: None,
+            capabilities: self.capabilities,
+        }
+    }
     /// Attempt to send the `Response`. This will only fail if the IPC body field of
     /// the `Response` has not yet been set using `body()` or `try_body()`.
     pub fn send(self) -> Result<(), BuildError> {
@@ -170,8 +385,356 @@ impl Response {
     }
 }
 
+/// An entry in the `errors` list of a GraphQL response, as produced by
+/// [`Response::graphql()`].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct GraphQLError {
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<GraphQLErrorLocation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path: Vec<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<serde_json::Value>,
+}
+
+impl GraphQLError {
+    pub fn new<T>(message: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            message: message.into(),
+            locations: vec![],
+            path: vec![],
+            extensions: None,
+        }
+    }
+}
+
+/// A location in a GraphQL query document that a [`GraphQLError`] refers to.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GraphQLErrorLocation {
+    pub line: u32,
+    pub column: u32,
+}
+
+/// The IPC body of a long-poll [`Response`], serialized to JSON as either
+/// `{"status":"timeout"}` or `{"status":"data","data":...}`. Clients should
+/// deserialize the body with `serde_json::from_slice::<LongPoll<T>>()` and
+/// re-issue the poll on [`LongPoll::Timeout`].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "status", content = "data", rename_all = "snake_case")]
+pub enum LongPoll<T> {
+    Timeout,
+    Data(T),
+}
+
+/// Parse an IPC body produced by [`Response::tagged()`] into its `type` tag
+/// and its (not yet deserialized) payload. A missing `payload` is read as `null`.
+pub fn parse_tagged(ipc: &[u8]) -> anyhow::Result<(String, serde_json::Value)> {
+    let mut body = serde_json::from_slice::<serde_json::Map<String, serde_json::Value>>(ipc)?;
+    let Some(serde_json::Value::String(tag)) = body.remove("type") else {
+        return Err(anyhow::anyhow!("tagged body has no string \"type\" field"));
+    };
+    let payload = body.remove("payload").unwrap_or_default();
+    Ok((tag, payload))
+}
+
+/// Merge two `Vary` header values, keeping each header name once
+/// (compared case-insensitively) in order of first appearance.
+fn merge_vary(existing: &str, new: &str) -> String {
+    let mut tokens: Vec<&str> = vec![];
+    for token in existing.split(',').chain(new.split(',')) {
+        let token = token.trim();
+        if !token.is_empty() && !tokens.iter().any(|t| t.eq_ignore_ascii_case(token)) {
+            tokens.push(token);
+        }
+    }
+    tokens.join(", ")
+}
+
+/// Whether an `Accept-Encoding` header value accepts gzip: either `gzip` (or
+/// `x-gzip`) is listed with a nonzero quality, or it is not listed and `*` is.
+fn accepts_gzip(accept_encoding: &str) -> bool {
+    let mut wildcard = false;
+    for coding in accept_encoding.split(',') {
+        let mut params = coding.split(';');
+        let name = params
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        let accepted = params
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .all(|q| q.trim().parse::<f32>().is_ok_and(|q| q > 0.0));
+        match name.as_str() {
+            "gzip" | "x-gzip" => return accepted,
+            "*" => wildcard = accepted,
+            _ => {}
+        }
+    }
+    wildcard
+}
+
 impl Default for Response {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::server::PromMetricType;
+
+    #[test]
+    fn test_prometheus() {
+        let response = Response::new().prometheus(&[
+            PromMetric::new("requests_total", PromMetricType::Counter, 3.0)
+                .help("Total requests.\nCounted per path.")
+                .label("path", "/a\"b\\c\n"),
+            PromMetric::new("temperature", PromMetricType::Gauge, f64::NEG_INFINITY),
+            PromMetric::new("requests_total", PromMetricType::Counter, 0.5),
+        ]);
+        let blob = response.blob.unwrap();
+        assert_eq!(blob.mime.as_deref(), Some("text/plain; version=0.0.4"));
+        assert_eq!(
+            String::from_utf8(blob.bytes).unwrap(),
+            "# HELP requests_total Total requests.\\nCounted per path.\n\
+             # TYPE requests_total counter\n\
+             requests_total{path=\"/a\\\"b\\\\c\\n\"} 3\n\
+             requests_total 0.5\n\
+             # TYPE temperature gauge\n\
+             temperature -Inf\n"
+        );
+    }
+
+    #[test]
+    fn test_prometheus_histogram() {
+        let response = Response::new().prometheus(&[
+            PromMetric::new("latency_bucket", PromMetricType::Histogram, 2.0).label("le", "0.5"),
+            PromMetric::new("latency_bucket", PromMetricType::Histogram, 3.0)
+                .label("le", "+Inf")
+                .help("Request latency."),
+            PromMetric::new("latency_sum", PromMetricType::Histogram, 1.25),
+            PromMetric::new("latency_count", PromMetricType::Histogram, 3.0),
+            PromMetric::new("rpc", PromMetricType::Summary, 0.1).label("quantile", "0.9"),
+            PromMetric::new("rpc_count", PromMetricType::Summary, 7.0),
+        ]);
+        assert_eq!(
+            String::from_utf8(response.blob.unwrap().bytes).unwrap(),
+            "# HELP latency Request latency.\n\
+             # TYPE latency histogram\n\
+             latency_bucket{le=\"0.5\"} 2\n\
+             latency_bucket{le=\"+Inf\"} 3\n\
+             latency_sum 1.25\n\
+             latency_count 3\n\
+             # TYPE rpc summary\n\
+             rpc{quantile=\"0.9\"} 0.1\n\
+             rpc_count 7\n"
+        );
+    }
+
+    #[test]
+    fn test_prometheus_names() {
+        let response = Response::new().prometheus(&[
+            PromMetric::new("http requests{total}", PromMetricType::Counter, 1.0)
+                .label("a:b \"c\"", "x"),
+            PromMetric::new("2xx:total", PromMetricType::Gauge, 2.0),
+        ]);
+        assert_eq!(
+            String::from_utf8(response.blob.unwrap().bytes).unwrap(),
+            "# TYPE http_requests_total_ counter\n\
+             http_requests_total_{a_b__c_=\"x\"} 1\n\
+             # TYPE _2xx:total gauge\n\
+             _2xx:total 2\n"
+        );
+    }
+
+    fn headers(response: Response) -> std::collections::HashMap<String, String> {
+        serde_json::from_slice::<HttpResponse>(&response.body.unwrap())
+            .unwrap()
+            .headers
+    }
+
+    #[test]
+    fn test_cors() {
+        let config = CorsConfig::new()
+            .allow_origin("*")
+            .allow_methods(&[http::Method::GET, http::Method::POST])
+            .max_age(600);
+        let h = headers(Response::new().cors(config.clone()));
+        assert_eq!(h["Access-Control-Allow-Origin"], "*");
+        assert_eq!(h["Access-Control-Allow-Methods"], "GET, POST");
+        assert_eq!(h["Access-Control-Max-Age"], "600");
+        assert!(!h.contains_key("Access-Control-Allow-Credentials"));
+
+        // `*` is invalid with credentials, so the request origin is reflected
+        let config = config
+            .allow_credentials(true)
+            .request_origin("https://a.example");
+        let h = headers(Response::new().cors(config));
+        assert_eq!(h["Access-Control-Allow-Origin"], "https://a.example");
+        assert_eq!(h["Access-Control-Allow-Credentials"], "true");
+        assert_eq!(h["Vary"], "Origin");
+
+        let config = CorsConfig::new()
+            .allow_origin("https://a.example")
+            .request_origin("https://b.example");
+        assert!(headers(Response::new().cors(config)).is_empty());
+    }
+
+    #[test]
+    fn test_http_headers_leave_other_bodies() {
+        let response = Response::new().body("not http").cors(
+            CorsConfig::new()
+                .allow_origin("https://a.example")
+                .request_origin("https://a.example"),
+        );
+        assert_eq!(response.body.unwrap(), b"not http");
+    }
+
+    #[test]
+    fn test_http_headers_merge_vary() {
+        let response = Response::new()
+            .http_headers([("vary", "Accept-Encoding")])
+            .http_headers([("Vary", "Origin, accept-encoding")]);
+        let h = headers(response);
+        assert_eq!(h.len(), 1);
+        assert_eq!(h["Vary"], "Accept-Encoding, Origin");
+    }
+
+    #[test]
+    fn test_http_headers_preserve_status() {
+        let response = Response::new()
+            .body(serde_json::to_vec(&HttpResponse::new(404u16).header("X-A", "1")).unwrap())
+            .http_headers([("X-B", "2")]);
+        let http_response =
+            serde_json::from_slice::<HttpResponse>(&response.body.unwrap()).unwrap();
+        assert_eq!(http_response.status, 404);
+        assert_eq!(http_response.headers.len(), 2);
+    }
+
+    #[test]
+    fn test_long_poll() {
+        let body = Response::new().long_poll_timeout().body.unwrap();
+        assert_eq!(body, br#"{"status":"timeout"}"#);
+        assert_eq!(
+            serde_json::from_slice::<LongPoll<Vec<u32>>>(&body).unwrap(),
+            LongPoll::Timeout
+        );
+        let body = Response::new()
+            .long_poll_data(&Vec::<u32>::new())
+            .unwrap()
+            .body
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<LongPoll<Vec<u32>>>(&body).unwrap(),
+            LongPoll::Data(vec![])
+        );
+    }
+
+    #[test]
+    fn test_static_asset() {
+        let serve = |accept_encoding| {
+            Response::new().static_asset(b"plain", Some(b"gz"), accept_encoding, "text/css")
+        };
+        let response = serve("deflate, gzip;q=0.8");
+        assert_eq!(response.blob.as_ref().unwrap().bytes, b"gz");
+        assert_eq!(
+            response.blob.as_ref().unwrap().mime.as_deref(),
+            Some("text/css")
+        );
+        assert_eq!(headers(response)["Content-Encoding"], "gzip");
+
+        for accept_encoding in ["", "br", "gzip;q=0", "*, gzip;q=0"] {
+            let response = serve(accept_encoding);
+            assert_eq!(response.blob.as_ref().unwrap().bytes, b"plain");
+            assert!(!headers(response).contains_key("Content-Encoding"));
+        }
+        assert_eq!(serve("*").blob.unwrap().bytes, b"gz");
+    }
+
+    #[test]
+    fn test_static_asset_with_cors() {
+        let response = Response::new()
+            .static_asset(b"plain", Some(b"gz"), "gzip", "text/css")
+            .cors(
+                CorsConfig::new()
+                    .allow_origin("https://a.example")
+                    .request_origin("https://a.example"),
+            );
+        let h = headers(response);
+        assert_eq!(h["Vary"], "Accept-Encoding, Origin");
+        assert_eq!(h["Content-Encoding"], "gzip");
+        assert_eq!(h["Access-Control-Allow-Origin"], "https://a.example");
+    }
+
+    #[test]
+    fn test_idempotency_key() {
+        let response = Response::new()
+            .metadata(r#"{"trace":"t1"}"#)
+            .idempotency_key("k1");
+        let metadata: serde_json::Value =
+            serde_json::from_str(response.metadata.as_deref().unwrap()).unwrap();
+        assert_eq!(
+            metadata,
+            serde_json::json!({"trace": "t1", "idempotency_key": "k1"})
+        );
+
+        let request = Request::new().metadata("plain").idempotency_key("k2");
+        let message = crate::Message::Request {
+            source: Address::new("our", ("a", "b", "c")),
+            expects_response: None,
+            body: vec![],
+            metadata: request.metadata,
+            capabilities: vec![],
+        };
+        assert_eq!(message.idempotency_key().as_deref(), Some("k2"));
+    }
+
+    #[test]
+    fn test_tagged() {
+        let body = Response::new()
+            .tagged("Transfer", &serde_json::json!({"amount": 5}))
+            .unwrap()
+            .body
+            .unwrap();
+        let (tag, payload) = parse_tagged(&body).unwrap();
+        assert_eq!(tag, "Transfer");
+        assert_eq!(payload, serde_json::json!({"amount": 5}));
+        assert!(parse_tagged(br#"{"payload": 1}"#).is_err());
+    }
+
+    #[test]
+    fn test_server_timing() {
+        let response = Response::new().server_timing(&[
+            ("db", 53.2),
+            ("cache hit", 0.0),
+            ("", 1.0),
+            ("bad", f64::NAN),
+            ("render", 4.0),
+        ]);
+        assert_eq!(
+            headers(response)["Server-Timing"],
+            "db;dur=53.2, cache_hit;dur=0, render;dur=4"
+        );
+    }
+
+    #[test]
+    fn test_graphql() {
+        let response = Response::new()
+            .graphql(Some(&serde_json::json!({"hero": "r2"})), &[])
+            .unwrap();
+        assert_eq!(response.body.unwrap(), br#"{"data":{"hero":"r2"}}"#);
+
+        let response = Response::new()
+            .graphql::<()>(None, &[GraphQLError::new("bad query")])
+            .unwrap();
+        assert_eq!(
+            response.body.unwrap(),
+            br#"{"data":null,"errors":[{"message":"bad query"}]}"#
+        );
+    }
+}